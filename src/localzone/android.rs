@@ -0,0 +1,19 @@
+//! Local timezone discovery for Android: the `persist.sys.timezone`
+//! system property already holds an IANA name.
+
+use anyhow::{anyhow, Result};
+use std::process::Command;
+
+use crate::tz_ok;
+
+/// Look for the local timezone using `getprop persist.sys.timezone`.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    let output = Command::new("getprop")
+        .arg("persist.sys.timezone")
+        .output()
+        .map_err(|e| anyhow!("could not run getprop: {}", e))?;
+    let name = String::from_utf8(output.stdout)
+        .map_err(|e| anyhow!("getprop output was not utf8: {}", e))?;
+    tz_ok(std::ffi::OsStr::new(name.trim()))
+}
@@ -0,0 +1,33 @@
+//! Local timezone discovery for the BSDs (FreeBSD, NetBSD, OpenBSD and
+//! DragonFly): `/var/db/zoneinfo` holds the zone name as plain text,
+//! written there by `tzsetup`; fall back to the `/etc/localtime` symlink
+//! target relative to the zoneinfo directory when that file is absent
+//! or doesn't name a known zone.
+
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+use crate::tz_ok;
+
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// Look for the local timezone using `/var/db/zoneinfo`, falling back
+/// to `/etc/localtime`.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    if let Ok(name) = std::fs::read_to_string("/var/db/zoneinfo") {
+        let name = name.trim();
+        if let Ok(zone) = tz_ok(std::ffi::OsStr::new(name)) {
+            return Ok(zone);
+        }
+    }
+
+    let path = std::fs::read_link("/etc/localtime")?;
+    let zoneinfo = PathBuf::from(ZONEINFO_DIR);
+    if let Ok(relative) = path.strip_prefix(&zoneinfo) {
+        if let Ok(zone) = tz_ok(relative.as_os_str()) {
+            return Ok(zone);
+        }
+    }
+    bail!("could not find local timezone")
+}
@@ -0,0 +1,22 @@
+//! Local timezone discovery for illumos: `/etc/default/init` has a
+//! `TZ=<name>` line set by `sysconfig`/`svccfg`.
+
+use anyhow::{bail, Result};
+
+use crate::tz_ok;
+
+/// Look for the local timezone in `/etc/default/init`.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    let contents = std::fs::read_to_string("/etc/default/init")?;
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(name) = line.strip_prefix("TZ=") {
+            let name = name.trim_matches('"');
+            if let Ok(zone) = tz_ok(std::ffi::OsStr::new(name)) {
+                return Ok(zone);
+            }
+        }
+    }
+    bail!("could not find local timezone")
+}
@@ -0,0 +1,53 @@
+//! Local timezone discovery for macOS: ask CoreFoundation directly via
+//! `CFTimeZoneCopySystem`/`CFTimeZoneGetName`, the same mechanism
+//! `iana-time-zone` uses. These are minimal hand-written bindings for
+//! the handful of CoreFoundation calls we need, rather than pulling in
+//! a full bindings crate for two functions.
+
+use anyhow::{anyhow, Result};
+use std::os::raw::{c_char, c_void};
+
+use crate::tz_ok;
+
+type CFTypeRef = *const c_void;
+type CFTimeZoneRef = CFTypeRef;
+type CFStringRef = CFTypeRef;
+type CFIndex = isize;
+
+const CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFTimeZoneCopySystem() -> CFTimeZoneRef;
+    fn CFTimeZoneGetName(tz: CFTimeZoneRef) -> CFStringRef;
+    fn CFStringGetCString(
+        the_string: CFStringRef,
+        buffer: *mut c_char,
+        buffer_size: CFIndex,
+        encoding: u32,
+    ) -> bool;
+    fn CFRelease(cf: CFTypeRef);
+}
+
+/// Look for the local timezone using `CFTimeZoneCopySystem`.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    unsafe {
+        let tz = CFTimeZoneCopySystem();
+        if tz.is_null() {
+            return Err(anyhow!("could not find local timezone"));
+        }
+        let name = CFTimeZoneGetName(tz);
+        let mut buf = [0 as c_char; 256];
+        let ok = CFStringGetCString(name, buf.as_mut_ptr(), buf.len() as CFIndex, CF_STRING_ENCODING_UTF8);
+        CFRelease(tz);
+        if !ok {
+            return Err(anyhow!("could not find local timezone"));
+        }
+        let cstr = std::ffi::CStr::from_ptr(buf.as_ptr());
+        let name = cstr
+            .to_str()
+            .map_err(|e| anyhow!("timezone name was not utf8: {}", e))?;
+        tz_ok(std::ffi::OsStr::new(name))
+    }
+}
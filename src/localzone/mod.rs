@@ -0,0 +1,99 @@
+//! Platform-specific local timezone discovery.
+//!
+//! Each supported OS has its own way of naming the system timezone, so
+//! this module mirrors the approach taken by the `iana-time-zone` crate:
+//! one backend per platform, dispatched by `cfg`. Every backend either
+//! returns a name that `parse_tz` accepts, or an error -- never a bare
+//! UTC offset or abbreviation that would silently resolve to the wrong
+//! zone.
+
+#[cfg(target_os = "linux")]
+mod unix;
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+mod bsd;
+
+#[cfg(target_os = "illumos")]
+mod illumos;
+
+#[cfg(target_os = "android")]
+mod android;
+
+#[cfg(target_os = "macos")]
+mod macos;
+
+#[cfg(windows)]
+mod windows;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+
+// Unlike the platform backends above, `windows_zones` is pure data plus a
+// binary search over it: it doesn't touch any Windows API, so it's built
+// and unit-tested on every platform instead of only under `cfg(windows)`.
+mod windows_zones;
+
+use anyhow::Result;
+
+/// Ask the OS for the local IANA timezone name.
+///
+#[cfg(target_os = "linux")]
+pub(crate) fn localzone_os() -> Result<String> {
+    unix::localzone_os()
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly"
+))]
+pub(crate) fn localzone_os() -> Result<String> {
+    bsd::localzone_os()
+}
+
+#[cfg(target_os = "illumos")]
+pub(crate) fn localzone_os() -> Result<String> {
+    illumos::localzone_os()
+}
+
+#[cfg(target_os = "android")]
+pub(crate) fn localzone_os() -> Result<String> {
+    android::localzone_os()
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn localzone_os() -> Result<String> {
+    macos::localzone_os()
+}
+
+#[cfg(windows)]
+pub(crate) fn localzone_os() -> Result<String> {
+    windows::localzone_os()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn localzone_os() -> Result<String> {
+    wasm::localzone_os()
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "dragonfly",
+    target_os = "illumos",
+    target_os = "android",
+    target_os = "macos",
+    windows,
+    target_arch = "wasm32"
+)))]
+pub(crate) fn localzone_os() -> Result<String> {
+    anyhow::bail!("local timezone discovery is not supported on this platform")
+}
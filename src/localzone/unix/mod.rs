@@ -0,0 +1,63 @@
+//! Local timezone discovery for Linux: try `/etc/timezone` (Debian's
+//! plain IANA name), then the `/etc/localtime` symlink target; if
+//! `/etc/localtime` is a regular file (a copy of the zoneinfo entry
+//! rather than a symlink to it), recover the zone name from its
+//! contents by matching its TZif transition table against every zone
+//! chrono-tz has compiled in. Rather than erroring out, fall back to
+//! UTC (as chrono's own `Local` does) if none of that identifies a zone.
+
+mod tzif;
+
+use anyhow::Result;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+use crate::tz_ok;
+
+const LOCALTIME: &str = "/etc/localtime";
+
+/// Look for the local timezone.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    if let Some(zone) = debian_timezone() {
+        return Ok(zone);
+    }
+    if let Some(zone) = symlink_target_zone() {
+        return Ok(zone);
+    }
+    if let Some(zone) = tzif::identify(LOCALTIME) {
+        return Ok(zone);
+    }
+    Ok("UTC".to_string())
+}
+
+/// Debian and its derivatives record the zone name directly in
+/// `/etc/timezone`.
+///
+fn debian_timezone() -> Option<String> {
+    let zone = std::fs::read_to_string("/etc/timezone").ok()?;
+    tz_ok(OsStr::new(zone.trim())).ok()
+}
+
+/// Recover the zone name from the `/etc/localtime` symlink target, on
+/// the many systems where it is one.
+///
+fn symlink_target_zone() -> Option<String> {
+    let path = std::fs::read_link(LOCALTIME).ok()?;
+    let mut dir = None;
+    let mut leaf = None;
+    for name in path.components() {
+        dir = leaf;
+        leaf = Some(name);
+    }
+    if let (Some(dir), Some(leaf)) = (dir, leaf) {
+        let mut zone = PathBuf::new();
+        zone.push(dir.as_os_str());
+        zone.push(leaf.as_os_str());
+        if let Ok(zone) = tz_ok(zone.as_os_str()) {
+            return Some(zone);
+        }
+    }
+    // try single-part timezone names such as "UTC"
+    leaf.and_then(|leaf| tz_ok(leaf.as_os_str()).ok())
+}
@@ -0,0 +1,238 @@
+//! A minimal reader for the binary TZif format ([RFC 8536]), used only
+//! to identify which compiled-in zone a copied `/etc/localtime` came
+//! from: read out its transition table, then look for the chrono-tz
+//! zone that has the same UTC offset at a fixed set of sample
+//! instants.
+//!
+//! [RFC 8536]: https://datatracker.ietf.org/doc/html/rfc8536
+
+use chrono::{Datelike, Offset, TimeZone, Utc};
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+const HEADER_LEN: usize = 44;
+
+struct Header {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+}
+
+fn i32_at(data: &[u8], offset: usize) -> Option<i32> {
+    u32_at(data, offset).map(|n| n as i32)
+}
+
+fn i64_at(data: &[u8], offset: usize) -> Option<i64> {
+    data.get(offset..offset + 8).map(|b| i64::from_be_bytes(b.try_into().unwrap()))
+}
+
+fn read_header(data: &[u8]) -> Option<Header> {
+    if data.get(..4)? != b"TZif" {
+        return None;
+    }
+    Some(Header {
+        version: *data.get(4)?,
+        isutcnt: u32_at(data, 20)? as usize,
+        isstdcnt: u32_at(data, 24)? as usize,
+        leapcnt: u32_at(data, 28)? as usize,
+        timecnt: u32_at(data, 32)? as usize,
+        typecnt: u32_at(data, 36)? as usize,
+        charcnt: u32_at(data, 40)? as usize,
+    })
+}
+
+/// Read the transition times (as `i64` seconds since the epoch) and
+/// the UTC offset active after each one, plus the number of bytes the
+/// header and the whole data block after it occupy together (the offset
+/// at which a following block's header starts).
+///
+fn read_block(data: &[u8], header: &Header, time_len: usize) -> Option<(Vec<(i64, i32)>, usize)> {
+    let read_time = |at: usize| -> Option<i64> {
+        if time_len == 8 {
+            i64_at(data, at)
+        } else {
+            i32_at(data, at).map(i64::from)
+        }
+    };
+    let mut offset = HEADER_LEN;
+    let mut times = Vec::with_capacity(header.timecnt);
+    for i in 0..header.timecnt {
+        times.push(read_time(offset + i * time_len)?);
+    }
+    offset += header.timecnt * time_len;
+    let mut type_idx = Vec::with_capacity(header.timecnt);
+    for i in 0..header.timecnt {
+        type_idx.push(*data.get(offset + i)? as usize);
+    }
+    offset += header.timecnt;
+    let mut utoff = Vec::with_capacity(header.typecnt);
+    for i in 0..header.typecnt {
+        utoff.push(i32_at(data, offset + i * 6)?);
+    }
+    offset += header.typecnt * 6;
+    offset += header.charcnt;
+    offset += header.leapcnt * (time_len + 4);
+    offset += header.isstdcnt;
+    offset += header.isutcnt;
+    if offset > data.len() {
+        return None;
+    }
+    let transitions = times
+        .into_iter()
+        .zip(type_idx)
+        .filter_map(|(time, idx)| utoff.get(idx).map(|&offset| (time, offset)))
+        .collect();
+    Some((transitions, offset))
+}
+
+/// Parse a TZif file's transition table, preferring the 64-bit block
+/// that a version 2 or 3 file appends after its legacy 32-bit one.
+///
+fn parse(data: &[u8]) -> Option<Vec<(i64, i32)>> {
+    let header = read_header(data)?;
+    let (transitions, block_len) = read_block(data, &header, 4)?;
+    if header.version == 0 {
+        return Some(transitions);
+    }
+    let v2 = data.get(block_len..)?;
+    let header2 = read_header(v2)?;
+    read_block(v2, &header2, 8).map(|(transitions, _)| transitions)
+}
+
+/// The UTC offset `transitions` says is in effect at `time`: the one
+/// set by the latest transition not after `time`, or by the earliest
+/// transition if `time` precedes all of them.
+///
+fn offset_at(transitions: &[(i64, i32)], time: i64) -> Option<i32> {
+    match transitions.binary_search_by_key(&time, |&(t, _)| t) {
+        Ok(i) => Some(transitions[i].1),
+        Err(0) => transitions.first().map(|&(_, offset)| offset),
+        Err(i) => Some(transitions[i - 1].1),
+    }
+}
+
+/// 1 January and 1 July (midnight UTC) of every year `transitions`
+/// covers, as a fixed set of instants to compare transition tables at.
+///
+/// Both a winter and a summer instant are needed: a zone that observes
+/// DST and one that doesn't can share the same standard (winter)
+/// offset, so sampling only January would identify a DST-observing
+/// zone as its DST-less neighbour for half of every year.
+///
+/// The range stops at `transitions`' own last entry rather than
+/// reaching into the future: TZif files only list transitions
+/// explicitly up to some finite year and fall back to a POSIX TZ rule
+/// string (which this reader doesn't interpret) to extrapolate further
+/// ones, so sampling past the last listed transition would compare a
+/// frozen, stale offset against chrono-tz's correctly-extrapolated one.
+///
+/// Going back further than 1972 would risk comparing pre-1972 local
+/// mean times, which different releases of the tz database round to
+/// different precisions, so the same zone could spuriously look like a
+/// mismatch just because the system's zoneinfo files are a different
+/// tzdata release than the one chrono-tz was compiled against.
+///
+fn sample_times(transitions: &[(i64, i32)]) -> impl Iterator<Item = i64> {
+    let last_year = transitions
+        .last()
+        .and_then(|&(time, _)| Utc.timestamp_opt(time, 0).single())
+        .map_or(1972, |dt| dt.year());
+    (1972..=last_year.max(1972)).flat_map(|year| {
+        [(year, 1, 1), (year, 7, 1)].into_iter().filter_map(|(year, month, day)| {
+            chrono::NaiveDate::from_ymd_opt(year, month, day)?
+                .and_hms_opt(0, 0, 0)
+                .map(|midnight| midnight.and_utc().timestamp())
+        })
+    })
+}
+
+/// Does `tz` have the same UTC offset as `transitions` at every
+/// sample instant?
+///
+fn matches(tz: Tz, transitions: &[(i64, i32)]) -> bool {
+    sample_times(transitions).all(|time| {
+        let expected = offset_at(transitions, time);
+        let actual = Utc
+            .timestamp_opt(time, 0)
+            .single()
+            .map(|instant| instant.with_timezone(&tz).offset().fix().local_minus_utc());
+        expected == actual
+    })
+}
+
+/// Read `path` as a TZif file and identify which compiled-in zone has
+/// the same transition table.
+///
+/// Many zones are pure aliases of one another (`America/New_York` and
+/// `America/Nassau`, say) and so tie on every sample instant; since a
+/// tie like that only happens between zones this function has just
+/// verified are offset-identical throughout the sampled range, any one
+/// of them names the right civil time and it doesn't matter which one
+/// is returned.
+///
+pub(super) fn identify(path: &str) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    let transitions = parse(&data)?;
+    TZ_VARIANTS.iter().find(|&&tz| matches(tz, &transitions)).map(|tz| tz.name().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_matches_transitions() {
+        // Assert against the actual transition table read from `path`,
+        // checking that the name `identify` returns parses and agrees
+        // with that table at every `sample_times` instant. These are the
+        // same instants `matches` samples, so this doesn't look between
+        // them for a difference the sampling would miss.
+        for name in ["America/New_York", "Europe/Paris", "Asia/Kolkata", "Pacific/Auckland"] {
+            let path = format!("/usr/share/zoneinfo/{}", name);
+            let data = std::fs::read(&path).expect("zoneinfo present on test host");
+            let transitions = parse(&data).expect("parseable TZif file");
+            let found = identify(&path).expect("zone identified from its transition table");
+            let tz: Tz = found.parse().expect("identify returns a valid IANA name");
+            for time in sample_times(&transitions) {
+                let expected = offset_at(&transitions, time);
+                let actual = Utc
+                    .timestamp_opt(time, 0)
+                    .single()
+                    .map(|instant| instant.with_timezone(&tz).offset().fix().local_minus_utc());
+                assert_eq!(expected, actual, "{found} disagrees with {name} at {time}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_identify_distinguishes_dst_from_fixed_offset() {
+        // A regression test for sampling only January 1st of each year:
+        // that cannot tell a DST-observing zone apart from a zone whose
+        // offset equals the DST zone's winter (standard) time every
+        // January but not every July (a fixed-offset zone, or, for
+        // America/Ensenada, one with no DST before 1976). Each of these
+        // used to be misidentified as the zone named alongside it.
+        for (name, not_this) in [
+            ("America/New_York", "America/Atikokan"),
+            ("Europe/Paris", "Africa/Bangui"),
+            ("Europe/London", "Africa/Abidjan"),
+            ("America/Los_Angeles", "America/Ensenada"),
+        ] {
+            let path = format!("/usr/share/zoneinfo/{}", name);
+            let found = identify(&path).expect("zone identified from its transition table");
+            assert_ne!(not_this, found);
+        }
+    }
+
+    #[test]
+    fn test_identify_missing_file() {
+        assert!(identify("/nonexistent/path/to/zoneinfo").is_none());
+    }
+}
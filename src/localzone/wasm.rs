@@ -0,0 +1,22 @@
+//! Local timezone discovery under wasm32: ask the host JS engine via
+//! `Intl.DateTimeFormat().resolvedOptions().timeZone`, the same
+//! mechanism `iana-time-zone` uses for its `wasm32-unknown-unknown` /
+//! `js` backend.
+
+use anyhow::{anyhow, Result};
+use js_sys::{Intl, Reflect};
+
+use crate::tz_ok;
+
+/// Look for the local timezone using `Intl.DateTimeFormat`.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    let format = Intl::DateTimeFormat::new(&js_sys::Array::new(), &js_sys::Object::new());
+    let options = format.resolved_options();
+    let time_zone = Reflect::get(&options, &"timeZone".into())
+        .map_err(|_| anyhow!("could not find local timezone"))?;
+    let name = time_zone
+        .as_string()
+        .ok_or_else(|| anyhow!("could not find local timezone"))?;
+    tz_ok(std::ffi::OsStr::new(&name))
+}
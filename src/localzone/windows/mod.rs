@@ -0,0 +1,43 @@
+//! Local timezone discovery for Windows: `GetTimeZoneInformation()`
+//! returns a Windows display name, which `canonize_tz` maps onto the
+//! nearest IANA equivalent using the CLDR `windowsZones` table.
+
+use anyhow::{anyhow, bail, Result};
+use std::ffi::OsString;
+
+/// Remove trailing `\u{0}` from the `u16` string returned by Windows.
+/// Inspired by <https://github.com/retep998/wio-rs/blob/master/src/wide.rs>
+fn from_wide_null(wide: &[u16]) -> OsString {
+    use std::os::windows::ffi::OsStringExt;
+
+    let len = wide.iter().take_while(|&&c| c != 0).count();
+    OsString::from_wide(&wide[..len])
+}
+
+/// Look for the local timezone using `GetTimeZoneInformation()`.
+///
+pub(crate) fn localzone_os() -> Result<String> {
+    use windows::Win32::System::Time::*;
+
+    let mut tz = TIME_ZONE_INFORMATION::default();
+    let e = unsafe { GetTimeZoneInformation(&mut tz) };
+    match e {
+        0 | 1 | 2 => {
+            let zone = from_wide_null(&tz.StandardName[..]);
+            let zone = zone.to_str();
+            match zone {
+                Some(s) => canonize_tz(s),
+                _ => bail!("could not find local timezone"),
+            }
+        }
+        _ => bail!("could not find local timezone"),
+    }
+}
+
+/// Windows timezone names aren't IANA names, so look them up in the
+/// CLDR `windowsZones` table.
+fn canonize_tz(zone: &str) -> Result<String> {
+    let iana = crate::localzone::windows_zones::lookup(zone)
+        .ok_or_else(|| anyhow!("no IANA timezone known for Windows zone {}", zone))?;
+    crate::tz_ok(std::ffi::OsStr::new(iana))
+}
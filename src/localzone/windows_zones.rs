@@ -0,0 +1,195 @@
+//! The CLDR `windowsZones` mapping from Windows timezone names to IANA
+//! names, embedded as a slice sorted by Windows name so it can be
+//! looked up with a binary search.
+//!
+//! Each row is `(windows name, territory, IANA name)`. Windows names
+//! are not 1:1 with IANA zones -- the same Windows name covers many
+//! countries with the same UTC offset and DST rules but different
+//! "canonical" zone -- so CLDR gives a `territory` of `"001"` (the
+//! world) as the default, plus extra rows for territories that want a
+//! different representative zone. Regenerate this table from CLDR's
+//! `windowsZones.xml` when a new Windows release adds zones.
+//!
+//! This module is compiled and unit-tested on every platform even
+//! though only the `cfg(windows)` backend calls `lookup`, since it's
+//! pure data plus a binary search and carries no Windows dependency --
+//! that's also why `lookup` is allowed to go unused outside Windows.
+
+#[cfg_attr(not(windows), allow(dead_code))]
+const WINDOWS_ZONES: &[(&str, &str, &str)] = &[
+    ("AUS Central Standard Time", "001", "Australia/Darwin"),
+    ("AUS Eastern Standard Time", "001", "Australia/Sydney"),
+    ("Afghanistan Standard Time", "001", "Asia/Kabul"),
+    ("Alaskan Standard Time", "001", "America/Anchorage"),
+    ("Aleutian Standard Time", "001", "America/Adak"),
+    ("Altai Standard Time", "001", "Asia/Barnaul"),
+    ("Arab Standard Time", "001", "Asia/Riyadh"),
+    ("Arabian Standard Time", "001", "Asia/Dubai"),
+    ("Arabic Standard Time", "001", "Asia/Baghdad"),
+    ("Argentina Standard Time", "001", "America/Buenos_Aires"),
+    ("Astrakhan Standard Time", "001", "Europe/Astrakhan"),
+    ("Atlantic Standard Time", "001", "America/Halifax"),
+    ("Aus Central W. Standard Time", "001", "Australia/Eucla"),
+    ("Azerbaijan Standard Time", "001", "Asia/Baku"),
+    ("Azores Standard Time", "001", "Atlantic/Azores"),
+    ("Bahia Standard Time", "001", "America/Bahia"),
+    ("Bangladesh Standard Time", "001", "Asia/Dhaka"),
+    ("Belarus Standard Time", "001", "Europe/Minsk"),
+    ("Bougainville Standard Time", "001", "Pacific/Bougainville"),
+    ("Canada Central Standard Time", "001", "America/Regina"),
+    ("Cape Verde Standard Time", "001", "Atlantic/Cape_Verde"),
+    ("Caucasus Standard Time", "001", "Asia/Yerevan"),
+    ("Cen. Australia Standard Time", "001", "Australia/Adelaide"),
+    ("Central America Standard Time", "001", "America/Guatemala"),
+    ("Central Asia Standard Time", "001", "Asia/Bishkek"),
+    ("Central Brazilian Standard Time", "001", "America/Cuiaba"),
+    ("Central Europe Standard Time", "001", "Europe/Budapest"),
+    ("Central European Standard Time", "001", "Europe/Warsaw"),
+    ("Central Pacific Standard Time", "001", "Pacific/Guadalcanal"),
+    ("Central Standard Time", "001", "America/Chicago"),
+    ("Central Standard Time (Mexico)", "001", "America/Mexico_City"),
+    ("Chatham Islands Standard Time", "001", "Pacific/Chatham"),
+    ("China Standard Time", "001", "Asia/Shanghai"),
+    ("Cuba Standard Time", "001", "America/Havana"),
+    ("Dateline Standard Time", "001", "Etc/GMT+12"),
+    ("E. Africa Standard Time", "001", "Africa/Nairobi"),
+    ("E. Australia Standard Time", "001", "Australia/Brisbane"),
+    ("E. Europe Standard Time", "001", "Europe/Chisinau"),
+    ("E. South America Standard Time", "001", "America/Sao_Paulo"),
+    ("Easter Island Standard Time", "001", "Pacific/Easter"),
+    ("Eastern Standard Time", "001", "America/New_York"),
+    ("Eastern Standard Time (Mexico)", "001", "America/Cancun"),
+    ("Egypt Standard Time", "001", "Africa/Cairo"),
+    ("Ekaterinburg Standard Time", "001", "Asia/Yekaterinburg"),
+    ("FLE Standard Time", "001", "Europe/Kiev"),
+    ("Fiji Standard Time", "001", "Pacific/Fiji"),
+    ("GMT Standard Time", "001", "Europe/London"),
+    ("GTB Standard Time", "001", "Europe/Bucharest"),
+    ("Georgian Standard Time", "001", "Asia/Tbilisi"),
+    ("Greenland Standard Time", "001", "America/Godthab"),
+    ("Greenwich Standard Time", "001", "Atlantic/Reykjavik"),
+    ("Haiti Standard Time", "001", "America/Port-au-Prince"),
+    ("Hawaiian Standard Time", "001", "Pacific/Honolulu"),
+    ("India Standard Time", "001", "Asia/Calcutta"),
+    ("Iran Standard Time", "001", "Asia/Tehran"),
+    ("Israel Standard Time", "001", "Asia/Jerusalem"),
+    ("Jordan Standard Time", "001", "Asia/Amman"),
+    ("Kaliningrad Standard Time", "001", "Europe/Kaliningrad"),
+    ("Kamchatka Standard Time", "001", "Asia/Kamchatka"),
+    ("Korea Standard Time", "001", "Asia/Seoul"),
+    ("Libya Standard Time", "001", "Africa/Tripoli"),
+    ("Line Islands Standard Time", "001", "Pacific/Kiritimati"),
+    ("Lord Howe Standard Time", "001", "Australia/Lord_Howe"),
+    ("Magadan Standard Time", "001", "Asia/Magadan"),
+    ("Magallanes Standard Time", "001", "America/Punta_Arenas"),
+    ("Marquesas Standard Time", "001", "Pacific/Marquesas"),
+    ("Mauritius Standard Time", "001", "Indian/Mauritius"),
+    ("Mid-Atlantic Standard Time", "001", "Etc/GMT+2"),
+    ("Middle East Standard Time", "001", "Asia/Beirut"),
+    ("Montevideo Standard Time", "001", "America/Montevideo"),
+    ("Morocco Standard Time", "001", "Africa/Casablanca"),
+    ("Mountain Standard Time", "001", "America/Denver"),
+    ("Mountain Standard Time (Mexico)", "001", "America/Chihuahua"),
+    ("Myanmar Standard Time", "001", "Asia/Rangoon"),
+    ("N. Central Asia Standard Time", "001", "Asia/Novosibirsk"),
+    ("Namibia Standard Time", "001", "Africa/Windhoek"),
+    ("Nepal Standard Time", "001", "Asia/Katmandu"),
+    ("New Zealand Standard Time", "001", "Pacific/Auckland"),
+    ("Newfoundland Standard Time", "001", "America/St_Johns"),
+    ("Norfolk Standard Time", "001", "Pacific/Norfolk"),
+    ("North Asia East Standard Time", "001", "Asia/Irkutsk"),
+    ("North Asia Standard Time", "001", "Asia/Krasnoyarsk"),
+    ("North Korea Standard Time", "001", "Asia/Pyongyang"),
+    ("Omsk Standard Time", "001", "Asia/Omsk"),
+    ("Pacific SA Standard Time", "001", "America/Santiago"),
+    ("Pacific Standard Time", "001", "America/Los_Angeles"),
+    ("Pacific Standard Time (Mexico)", "001", "America/Tijuana"),
+    ("Pakistan Standard Time", "001", "Asia/Karachi"),
+    ("Paraguay Standard Time", "001", "America/Asuncion"),
+    ("Qyzylorda Standard Time", "001", "Asia/Qyzylorda"),
+    ("Romance Standard Time", "001", "Europe/Paris"),
+    ("Russia Time Zone 10", "001", "Asia/Srednekolymsk"),
+    ("Russia Time Zone 11", "001", "Asia/Kamchatka"),
+    ("Russia Time Zone 3", "001", "Europe/Samara"),
+    ("Russian Standard Time", "001", "Europe/Moscow"),
+    ("SA Eastern Standard Time", "001", "America/Cayenne"),
+    ("SA Pacific Standard Time", "001", "America/Bogota"),
+    ("SA Western Standard Time", "001", "America/La_Paz"),
+    ("SE Asia Standard Time", "001", "Asia/Bangkok"),
+    ("Saint Pierre Standard Time", "001", "America/Miquelon"),
+    ("Sakhalin Standard Time", "001", "Asia/Sakhalin"),
+    ("Samoa Standard Time", "001", "Pacific/Apia"),
+    ("Sao Tome Standard Time", "001", "Africa/Sao_Tome"),
+    ("Saratov Standard Time", "001", "Europe/Saratov"),
+    ("Singapore Standard Time", "001", "Asia/Singapore"),
+    ("South Africa Standard Time", "001", "Africa/Johannesburg"),
+    ("Sri Lanka Standard Time", "001", "Asia/Colombo"),
+    ("Sudan Standard Time", "001", "Africa/Khartoum"),
+    ("Syria Standard Time", "001", "Asia/Damascus"),
+    ("Taipei Standard Time", "001", "Asia/Taipei"),
+    ("Tasmania Standard Time", "001", "Australia/Hobart"),
+    ("Tocantins Standard Time", "001", "America/Araguaina"),
+    ("Tokyo Standard Time", "001", "Asia/Tokyo"),
+    ("Tomsk Standard Time", "001", "Asia/Tomsk"),
+    ("Tonga Standard Time", "001", "Pacific/Tongatapu"),
+    ("Transbaikal Standard Time", "001", "Asia/Chita"),
+    ("Turkey Standard Time", "001", "Europe/Istanbul"),
+    ("Turks And Caicos Standard Time", "001", "America/Grand_Turk"),
+    ("US Eastern Standard Time", "001", "America/Indianapolis"),
+    ("US Mountain Standard Time", "001", "America/Phoenix"),
+    ("UTC", "001", "Etc/UTC"),
+    ("UTC+12", "001", "Etc/GMT-12"),
+    ("UTC+13", "001", "Etc/GMT-13"),
+    ("UTC-02", "001", "Etc/GMT+2"),
+    ("UTC-08", "001", "Etc/GMT+8"),
+    ("UTC-09", "001", "Etc/GMT+9"),
+    ("UTC-11", "001", "Etc/GMT+11"),
+    ("Ulaanbaatar Standard Time", "001", "Asia/Ulaanbaatar"),
+    ("Venezuela Standard Time", "001", "America/Caracas"),
+    ("Vladivostok Standard Time", "001", "Asia/Vladivostok"),
+    ("Volgograd Standard Time", "001", "Europe/Volgograd"),
+    ("W. Australia Standard Time", "001", "Australia/Perth"),
+    ("W. Central Africa Standard Time", "001", "Africa/Lagos"),
+    ("W. Europe Standard Time", "001", "Europe/Berlin"),
+    ("W. Mongolia Standard Time", "001", "Asia/Hovd"),
+    ("West Asia Standard Time", "001", "Asia/Tashkent"),
+    ("West Bank Standard Time", "001", "Asia/Hebron"),
+    ("West Pacific Standard Time", "001", "Pacific/Port_Moresby"),
+    ("Yakutsk Standard Time", "001", "Asia/Yakutsk"),
+];
+
+/// Look up the IANA name for a Windows timezone name.
+///
+/// Windows names map to several candidate IANA zones depending on
+/// territory; since we only have the bare name from
+/// `GetTimeZoneInformation`, we use the CLDR `"001"` (world) default.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn lookup(windows_name: &str) -> Option<&'static str> {
+    WINDOWS_ZONES
+        .binary_search_by_key(&windows_name, |(name, _, _)| name)
+        .ok()
+        .map(|i| WINDOWS_ZONES[i].2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_ok() {
+        assert_eq!(lookup("Pacific Standard Time"), Some("America/Los_Angeles"));
+        assert_eq!(lookup("W. Europe Standard Time"), Some("Europe/Berlin"));
+    }
+
+    #[test]
+    fn test_lookup_nok() {
+        assert_eq!(lookup("nonexistent"), None);
+    }
+
+    #[test]
+    fn test_windows_zones_sorted() {
+        // `lookup` binary-searches this table, so it must stay sorted by
+        // Windows name for every row to actually be reachable.
+        assert!(WINDOWS_ZONES.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    }
+}
@@ -5,30 +5,38 @@
 //!
 //!     datez <time> <zone>...
 //!
-//! You should write the time in ISO 8601 / RFC 3339 format but
-//! _without_ a UTC offset, and list as many tz database timezone names
-//! as you want.
+//! Write the time in ISO 8601 / RFC 3339 format, and list as many tz
+//! database timezone names as you want. `now`, `today`, `tomorrow` and
+//! `yesterday` are also understood, as are Unix epoch timestamps
+//! (`@1610000000`) and signed offsets from now (`+3h`, `-90m`,
+//! `+2d 4h`).
 //!
-//! The time is read using the first timezone; it is converted to UTC and
-//! printed in UTC and in every timezone you listed, and in your local
-//! timezone (if possible).
+//! If the time is one of the keywords above, carries a UTC offset
+//! (`+02:00`, `-0500`, or `Z`), is an epoch timestamp, or is a relative
+//! offset from now, it already names an absolute instant, so it is
+//! read directly (anchored to the system clock, not to any listed
+//! zone) and the zone list is optional; otherwise it is read using the
+//! first listed zone. Either way it is converted to UTC and printed in
+//! UTC and in every timezone you listed, and in your local timezone
+//! (if possible).
 //!
 //! The local timezone is discovered from the `TZ` environment variable
 //! if that is set, or by an OS-specific mechanism; it isn't an error
 //! if neither of those work, but you have to list your timezone
 //! explicitly.
 //!
-//! On Unix, `datez` reads the symlink at `/etc/localtime`.
-//!
-//! On Windows, `datez` calls Win32 `GetTimeZoneInformation()`.
+//! The OS-specific mechanism is handled by the [`localzone`] module,
+//! which has a separate backend for Linux, the BSDs, illumos, Android,
+//! macOS, Windows and wasm.
+
+mod localzone;
+mod tzspec;
 
 use anyhow::{anyhow, bail, Result};
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
-use chrono_tz::Tz;
+use chrono::{DateTime, Duration, FixedOffset, Local, LocalResult, NaiveDateTime, TimeZone};
 use std::collections::HashMap;
 use std::ffi::OsStr;
-#[cfg(windows)]
-use std::ffi::OsString;
+use tzspec::TzSpec;
 
 /// Try several formats for parsing time
 ///
@@ -54,38 +62,147 @@ fn parse_time(arg: &str) -> Result<NaiveDateTime> {
     bail!("time must be in RFC 3339 / ISO 8601 format, without a UTC offset");
 }
 
-/// Map an IANA TZDB timezone string into a Tz object
+/// Recognise `now`, `today`, `tomorrow` and `yesterday`, anchored to
+/// the system's own local time zone rather than whichever zone is
+/// named on the command line: these already name an absolute instant
+/// (or, for the day keywords, local midnight), so resolving them
+/// against the first listed zone instead would silently treat the
+/// local wall clock as if it were that zone's wall clock.
+///
+fn parse_keyword_time(arg: &str) -> Option<DateTime<FixedOffset>> {
+    let now = Local::now();
+    let midnight = |days| {
+        let date = now.date_naive() + Duration::days(days);
+        Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).single()
+    };
+    match arg {
+        "now" => Some(now),
+        "today" => midnight(0),
+        "tomorrow" => midnight(1),
+        "yesterday" => midnight(-1),
+        _ => None,
+    }
+    .map(|dt| dt.fixed_offset())
+}
+
+/// Try to parse a time that already carries a UTC offset (`+02:00`,
+/// `-0500`, `Z`), tolerating a missing minutes field the way chrono's
+/// permissive `%#z` does (e.g. `+02`).
+///
+fn parse_offset_time(arg: &str) -> Option<DateTime<FixedOffset>> {
+    let fmts = ["%FT%T%#z", "%F %T%#z", "%F.%T%#z"];
+    fmts.into_iter().find_map(|fmt| DateTime::parse_from_str(arg, fmt).ok())
+}
+
+/// Try to parse an epoch timestamp (`@1610000000`, optionally with
+/// fractional seconds) or a signed offset from the current instant
+/// (`+3h`, `-90m`, `+2d 4h`). Like [`parse_offset_time`], this already
+/// names an absolute instant, so no timezone is needed to resolve it.
+///
+fn parse_relative_time(arg: &str) -> Option<DateTime<FixedOffset>> {
+    let utc = match arg.strip_prefix('@') {
+        Some(epoch) => {
+            let secs: f64 = epoch.parse().ok()?;
+            DateTime::from_timestamp(secs.trunc() as i64, (secs.fract() * 1e9).round() as u32)?
+        }
+        None => chrono::Utc::now() + parse_offset_duration(arg)?,
+    };
+    Some(utc.with_timezone(&FixedOffset::east_opt(0).unwrap()))
+}
+
+/// Parse a run of signed durations such as `+3h`, `-90m` or `+2d 4h`,
+/// where a term with no sign of its own repeats the previous term's
+/// sign.
+///
+fn parse_offset_duration(arg: &str) -> Option<Duration> {
+    let mut total = Duration::zero();
+    let mut sign = 0;
+    for term in arg.split_whitespace() {
+        let (term, term_sign) = match term.strip_prefix('+') {
+            Some(rest) => (rest, 1),
+            None => match term.strip_prefix('-') {
+                Some(rest) => (rest, -1),
+                None => (term, sign),
+            },
+        };
+        if term_sign == 0 {
+            return None;
+        }
+        sign = term_sign;
+        let split = term.find(|c: char| !c.is_ascii_digit())?;
+        let (digits, unit) = term.split_at(split);
+        let count: i64 = digits.parse().ok()?;
+        let unit = match unit {
+            "s" => Duration::seconds(count),
+            "m" => Duration::minutes(count),
+            "h" => Duration::hours(count),
+            "d" => Duration::days(count),
+            "w" => Duration::weeks(count),
+            _ => return None,
+        };
+        total += unit * sign;
+    }
+    (sign != 0).then_some(total)
+}
+
+/// Map a timezone string, either an IANA TZDB name or a POSIX `TZ`
+/// string, into a `TzSpec`
 ///
-fn parse_tz(zone: &str) -> Result<Tz> {
-    zone.parse().map_err(|e| anyhow!("{}", e))
+fn parse_tz(zone: &str) -> Result<TzSpec> {
+    TzSpec::parse(zone)
 }
 
 /// Validate the timezone
 ///
-fn tz_ok(zone: &OsStr) -> Result<String> {
+pub(crate) fn tz_ok(zone: &OsStr) -> Result<String> {
     let zone = zone.to_str().ok_or_else(|| anyhow!("not utf8"))?;
     parse_tz(zone).map(|_| zone.to_owned())
 }
 
-/// Parse the time and set its timezone
+/// Parse the time and resolve it in its timezone. A local time close
+/// to a DST transition can map to more than one instant (`Ambiguous`,
+/// in the fall-back overlap) or to none at all (`None`, in the
+/// spring-forward gap).
+///
+fn get_time(time: &str, tz: &TzSpec) -> Result<LocalResult<DateTime<FixedOffset>>> {
+    let naive = parse_time(time)?;
+    Ok(tz.resolve_local(&naive))
+}
+
+/// The two instants immediately either side of a local-time gap,
+/// found by stepping away from the missing wall-clock time a second
+/// at a time until it resolves again.
 ///
-fn get_time(time: &str, zone: &str, tz: &Tz) -> Result<DateTime<Tz>> {
+fn straddle_gap(time: &str, tz: &TzSpec) -> Result<(DateTime<FixedOffset>, DateTime<FixedOffset>)> {
     let naive = parse_time(time)?;
-    tz.from_local_datetime(&naive).single().ok_or_else(|| {
-        anyhow!("could not convert {} to {} timezone", time, zone)
-    })
+    let mut before = naive;
+    let before = loop {
+        before -= Duration::seconds(1);
+        if let Some(dt) = tz.resolve_local(&before).single() {
+            break dt;
+        }
+    };
+    let mut after = naive;
+    let after = loop {
+        after += Duration::seconds(1);
+        if let Some(dt) = tz.resolve_local(&after).single() {
+            break dt;
+        }
+    };
+    Ok((before, after))
 }
 
 /// Prints the specified time with its plain text timezone
 ///
-fn print_time_tz(time: &DateTime<Tz>, zone: &str, tz: &Tz) {
-    let time = time.with_timezone(tz);
+fn print_time_tz(time: &DateTime<FixedOffset>, zone: &str, tz: &TzSpec) {
+    let offset = tz.offset_at(&time.with_timezone(&chrono::Utc));
+    let time = time.with_timezone(&offset);
     println!("{} ({})", time.format("%F.%T%z"), zone);
 }
 
 /// Extracts the timezone before printing the result
 ///
-fn print_time(time: &DateTime<Tz>, zone: &str) -> Result<()> {
+fn print_time(time: &DateTime<FixedOffset>, zone: &str) -> Result<()> {
     let tz = parse_tz(zone)?;
     print_time_tz(time, zone, &tz);
     Ok(())
@@ -97,82 +214,10 @@ fn localzone() -> Result<String> {
     if let Some(zone) = std::env::var_os("TZ") {
         tz_ok(&zone)
     } else {
-        localzone_os()
-    }
-}
-
-/// Look for the local timezone using `/etc/localtime`
-///
-#[cfg(unix)]
-fn localzone_os() -> Result<String> {
-    use std::path::PathBuf;
-
-    let path = std::fs::read_link("/etc/localtime")?;
-    let mut dir = None;
-    let mut leaf = None;
-    for name in path.components() {
-        dir = leaf;
-        leaf = Some(name);
-    }
-    if let (Some(dir), Some(leaf)) = (dir, leaf) {
-        let mut zone = PathBuf::new();
-        zone.push(dir.as_os_str());
-        zone.push(leaf.as_os_str());
-        if let Ok(zone) = tz_ok(zone.as_os_str()) {
-            return Ok(zone);
-        }
-    }
-    // try single-part timezone names such as "UTC"
-    if let Some(leaf) = leaf {
-        return tz_ok(leaf.as_os_str());
-    }
-    bail!("could not find local timezone")
-}
-
-/// Remove trailing \u{0} from \u16 string returned by Windows
-/// Inspired from https://github.com/retep998/wio-rs/blob/master/src/wide.rs
-#[cfg(windows)]
-fn from_wide_null(wide: &[u16]) -> OsString {
-    use std::os::windows::ffi::OsStringExt;
-
-    let len = wide.iter().take_while(|&&c| c != 0).count();
-    OsString::from_wide(&wide[..len])
-}
-
-/// Look for the local timezone using `GetTimeZoneInformation()`
-///
-#[cfg(windows)]
-fn localzone_os() -> Result<String> {
-    use windows::Win32::System::Time::*;
-
-    let mut tz = TIME_ZONE_INFORMATION::default();
-    let e = unsafe { GetTimeZoneInformation(&mut tz) };
-    match e {
-        0 | 1 | 2 => {
-            let zone = from_wide_null(&tz.StandardName[..]);
-            let zone = zone.to_str();
-            // Fix some timezones
-            match zone {
-                Some(s) => canonize_tz(s),
-                _ => bail!("could not find local timezone"),
-            }
-        }
-        _ => bail!("could not find local timezone"),
+        localzone::localzone_os()
     }
 }
 
-/// Windows timezones are in some case completely different from the rest of world
-/// so fix it for known cases.
-#[cfg(windows)]
-fn canonize_tz(zone: &str) -> Result<String> {
-    // XXX will probably evolve into a hash if other cases appear
-    if zone == "Romance Standard Time" {
-        return Ok("Europe/Paris".to_string());
-    }
-    let z = OsStr::new(zone);
-    tz_ok(z)
-}
-
 /// Process the command line
 ///
 fn main() -> Result<()> {
@@ -183,44 +228,77 @@ fn main() -> Result<()> {
         }
         args.push(zone);
     }
-    if args.len() < 3 || args[1] == "-h" || args[1] == "--help" {
+    if args.len() < 2 || args[1] == "-h" || args[1] == "--help" {
+        bail!("usage: datez <datetime> [tz]...");
+    }
+    if let Some(time) = parse_keyword_time(&args[1])
+        .or_else(|| parse_offset_time(&args[1]))
+        .or_else(|| parse_relative_time(&args[1]))
+    {
+        return print_all_zones(&time, &args[2..]);
+    }
+    if args.len() < 3 {
         bail!("usage: datez <datetime> <tz>...");
     }
-    let time = get_time(&args[1], &args[2], &parse_tz(&args[2])?)?;
+    let tz = parse_tz(&args[2])?;
+    match get_time(&args[1], &tz)? {
+        LocalResult::Single(time) => print_all_zones(&time, &args[2..]),
+        LocalResult::Ambiguous(earlier, later) => {
+            println!(
+                "{} is ambiguous in {}: it happens twice, as clocks go back",
+                args[1], args[2]
+            );
+            println!("-- earlier, at {} --", earlier.format("%z"));
+            print_all_zones(&earlier, &args[2..])?;
+            println!("-- later, at {} --", later.format("%z"));
+            print_all_zones(&later, &args[2..])
+        }
+        LocalResult::None => {
+            let (before, after) = straddle_gap(&args[1], &tz)?;
+            bail!(
+                "{} does not exist in {}: clocks jump from {} to {}",
+                args[1],
+                args[2],
+                before.format("%F.%T%z"),
+                after.format("%F.%T%z")
+            );
+        }
+    }
+}
+
+/// Print the resolved instant in UTC and in every zone listed on the
+/// command line, skipping duplicates.
+///
+fn print_all_zones(time: &DateTime<FixedOffset>, zones: &[String]) -> Result<()> {
     let mut dedup = HashMap::new();
-    print_time(&time, "UTC")?;
+    print_time(time, "UTC")?;
     dedup.insert("UTC".to_string(), ());
-    for arg in args[2..].iter() {
-        if !dedup.contains_key(arg) {
-            print_time(&time, arg)?;
-            dedup.insert(arg.to_string(), ());
+    for zone in zones {
+        if !dedup.contains_key(zone) {
+            print_time(time, zone)?;
+            dedup.insert(zone.to_string(), ());
         }
     }
     Ok(())
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
 
     use rstest::rstest;
 
     #[test]
-    #[cfg(unix)]
+    #[cfg(target_os = "linux")]
     fn test_localzone() {
         // this all needs to be one test function, because tests are run
         // in parallel on multiple threads, which is incompatible with
         // manipulating environment variables
 
         std::env::remove_var("TZ");
-        let path = std::fs::read_link("/etc/localtime");
-        let zone = localzone();
-        match (&path, &zone) {
-            (Ok(path), Ok(zone)) => assert!(path.ends_with(zone)),
-            (Err(_), Err(_)) => (), // plausible
-            _ => panic!("inconsistent localzone: {:?} / {:?}", path, zone),
-        }
+        // localzone_os() always finds a zone or falls back to UTC, so
+        // it should never error once TZ is out of the way.
+        assert!(localzone().is_ok());
 
         std::env::set_var("TZ", "Europe/Paris");
         let tz = localzone();
@@ -248,6 +326,82 @@ mod tests {
         assert!(parse_time(s).is_ok());
     }
 
+    #[rstest]
+    #[case("now")]
+    #[case("today")]
+    #[case("tomorrow")]
+    #[case("yesterday")]
+    fn test_parse_keyword_time_ok(#[case] s: &str) {
+        assert!(parse_keyword_time(s).is_some());
+    }
+
+    #[test]
+    fn test_parse_keyword_time_today_is_midnight() {
+        assert_eq!(
+            parse_keyword_time("today").unwrap().time(),
+            parse_keyword_time("tomorrow").unwrap().time()
+        );
+        assert_eq!(
+            chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            parse_keyword_time("today").unwrap().time()
+        );
+    }
+
+    #[test]
+    fn test_parse_keyword_time_anchors_to_system_zone_not_argument_zone() {
+        // "now" must name the same absolute instant regardless of which
+        // zone it is later printed in: resolving it against the first
+        // listed zone (rather than the system's own clock) would shift
+        // it by that zone's offset instead of leaving it alone.
+        let now = parse_keyword_time("now").unwrap();
+        let system_now = chrono::Utc::now();
+        assert!((now.with_timezone(&chrono::Utc) - system_now).num_seconds().abs() <= 1);
+    }
+
+    #[rstest]
+    #[case("@1610000000", 1610000000)]
+    #[case("@1610000000.5", 1610000000)]
+    fn test_parse_relative_time_epoch(#[case] s: &str, #[case] secs: i64) {
+        let time = parse_relative_time(s).unwrap();
+        assert_eq!(secs, time.timestamp());
+    }
+
+    #[rstest]
+    #[case("+3h")]
+    #[case("-90m")]
+    #[case("+2d 4h")]
+    #[case("-1w")]
+    fn test_parse_relative_time_offset_ok(#[case] s: &str) {
+        assert!(parse_relative_time(s).is_some());
+    }
+
+    #[rstest]
+    #[case("3h")]
+    #[case("+3x")]
+    #[case("bad")]
+    #[case("")]
+    fn test_parse_relative_time_nok(#[case] s: &str) {
+        assert!(parse_relative_time(s).is_none());
+    }
+
+    #[rstest]
+    #[case("2021-07-21T16:00:00+02:00")]
+    #[case("2021-07-21T16:00:00+02")]
+    #[case("2021-07-21T16:00:00-0500")]
+    #[case("2021-07-21T16:00:00Z")]
+    #[case("2021-07-21 16:00:00Z")]
+    #[case("2021-07-21.16:00:00Z")]
+    fn test_parse_offset_time_ok(#[case] s: &str) {
+        assert!(parse_offset_time(s).is_some());
+    }
+
+    #[rstest]
+    #[case("2021-07-21T16:00:00")]
+    #[case("bad")]
+    fn test_parse_offset_time_nok(#[case] s: &str) {
+        assert!(parse_offset_time(s).is_none());
+    }
+
     #[rstest]
     #[case("Europe/Paris")]
     #[case("Europe/London")]
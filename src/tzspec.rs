@@ -0,0 +1,54 @@
+//! A timezone specifier: either a name from the IANA tz database, or a
+//! POSIX `TZ` string with its own DST transition rule. Both sides are
+//! usable wherever `datez` previously only accepted a `chrono_tz::Tz`.
+
+mod posix;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+pub(crate) use posix::PosixTz;
+
+/// Either an IANA zone name (`Europe/Paris`) or a POSIX `TZ` rule
+/// (`EST5EDT,M3.2.0/2,M11.1.0/2`).
+#[derive(Debug, Clone)]
+pub(crate) enum TzSpec {
+    Iana(Tz),
+    Posix(PosixTz),
+}
+
+impl TzSpec {
+    /// Parse a zone name, trying the IANA tz database first and
+    /// falling back to a POSIX `TZ` string.
+    ///
+    pub(crate) fn parse(zone: &str) -> Result<TzSpec> {
+        if let Ok(tz) = zone.parse::<Tz>() {
+            return Ok(TzSpec::Iana(tz));
+        }
+        posix::parse(zone)
+            .map(TzSpec::Posix)
+            .map_err(|e| anyhow!("{} is not a tz database name or a POSIX TZ string: {}", zone, e))
+    }
+
+    /// Resolve a naive wall-clock time to an instant in this zone.
+    ///
+    pub(crate) fn resolve_local(&self, naive: &NaiveDateTime) -> LocalResult<DateTime<FixedOffset>> {
+        match self {
+            TzSpec::Iana(tz) => tz
+                .from_local_datetime(naive)
+                .map(|dt| dt.with_timezone(&dt.offset().fix())),
+            TzSpec::Posix(tz) => tz.resolve_local(naive),
+        }
+    }
+
+    /// The offset this zone has at a given instant, for printing that
+    /// instant in this zone's local time.
+    ///
+    pub(crate) fn offset_at(&self, instant: &DateTime<Utc>) -> FixedOffset {
+        match self {
+            TzSpec::Iana(tz) => instant.with_timezone(tz).offset().fix(),
+            TzSpec::Posix(tz) => tz.offset_at(instant),
+        }
+    }
+}
@@ -0,0 +1,482 @@
+//! Parse and evaluate POSIX `TZ` strings, e.g. `EST5EDT,M3.2.0/2,M11.1.0/2`
+//! or `<+07>-7`.
+//!
+//! Format: `std offset[dst[offset][,start[/time],end[/time]]]`. `std`
+//! and `dst` are 3+ letter abbreviations or `<...>` quoted strings;
+//! `offset` is `[+-]hh[:mm[:ss]]` with the sign *inverted* from the
+//! usual convention (positive means west of UTC, matching `TZ`'s
+//! historical origin as "hours you add to local time to get UTC");
+//! `start`/`end` are `Mm.w.d` (month, week 1-5 with 5 meaning last,
+//! Sunday-based weekday), `Jn` (day of year, 1-365, never counting
+//! Feb 29), or `n` (day of year, 0-365, counting Feb 29), each
+//! optionally followed by `/time` (default `02:00:00`).
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// A named UTC offset, e.g. `EST` at `-5:00`. The name itself isn't
+/// used for anything -- `datez` always prints the zone string the
+/// user typed -- but POSIX requires every offset to be named, so we
+/// parse and discard it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NamedOffset {
+    offset: FixedOffset,
+}
+
+/// A single DST transition: the day it falls on, and the local time
+/// of day (seconds after midnight) it takes effect at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Transition {
+    day: DayRule,
+    time: i32,
+}
+
+/// How a transition day is specified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DayRule {
+    /// `Mm.w.d`: month `m`, week `w` (1-5, 5 = last), weekday `d`
+    /// (0 = Sunday).
+    MonthWeekDay { month: u32, week: u32, weekday: u32 },
+    /// `Jn`: day of year 1-365, Feb 29 is never counted.
+    JulianNoLeap(u16),
+    /// `n`: day of year 0-365, Feb 29 is counted when present.
+    Julian(u16),
+}
+
+impl DayRule {
+    fn date_in(&self, year: i32) -> NaiveDate {
+        match *self {
+            DayRule::MonthWeekDay { month, week, weekday } => {
+                month_week_day(year, month, week, weekday)
+            }
+            DayRule::JulianNoLeap(day) => {
+                let ordinal = if is_leap_year(year) && day >= 60 {
+                    day + 1
+                } else {
+                    day
+                };
+                NaiveDate::from_yo_opt(year, ordinal as u32).expect("valid Jn day")
+            }
+            DayRule::Julian(day) => {
+                NaiveDate::from_yo_opt(year, day as u32 + 1).expect("valid n day")
+            }
+        }
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// The `n`th (or last, for `week == 5`) `weekday` of `month` in `year`.
+fn month_week_day(year: i32, month: u32, week: u32, weekday: u32) -> NaiveDate {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month");
+    let first_weekday = first.weekday().num_days_from_sunday();
+    if week == 5 {
+        let next_month = NaiveDate::from_ymd_opt(year, month, 28)
+            .expect("valid month")
+            .iter_days()
+            .find(|d| d.month() != month)
+            .expect("month has a successor");
+        let last = next_month.pred_opt().expect("month has a last day");
+        let back = (last.weekday().num_days_from_sunday() + 7 - weekday) % 7;
+        last - Duration::days(back as i64)
+    } else {
+        let forward = (weekday + 7 - first_weekday) % 7;
+        first + Duration::days((forward + (week - 1) * 7) as i64)
+    }
+}
+
+/// A DST rule: when it starts and ends, each in that side's own local
+/// wall-clock time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DstRule {
+    start: Transition,
+    end: Transition,
+}
+
+/// A parsed POSIX `TZ` string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PosixTz {
+    std: NamedOffset,
+    dst: Option<(NamedOffset, DstRule)>,
+}
+
+impl PosixTz {
+    /// The offset this zone has at a given instant.
+    ///
+    pub(crate) fn offset_at(&self, instant: &DateTime<Utc>) -> FixedOffset {
+        let (dst, rule) = match &self.dst {
+            None => return self.std.offset,
+            Some(pair) => pair,
+        };
+        // Approximate the local wall clock as if std were in effect,
+        // good enough to tell which side of the year's transitions we're on.
+        let naive = instant.naive_utc() + Duration::seconds(self.std.offset.local_minus_utc() as i64);
+        if active_approx(rule, &naive, self.std.offset, dst.offset) {
+            dst.offset
+        } else {
+            self.std.offset
+        }
+    }
+
+    /// Resolve a naive wall-clock time to an instant in this zone.
+    ///
+    pub(crate) fn resolve_local(&self, naive: &NaiveDateTime) -> LocalResult<DateTime<FixedOffset>> {
+        let (dst, rule) = match &self.dst {
+            None => return self.std.offset.from_local_datetime(naive),
+            Some(pair) => pair,
+        };
+
+        let delta = dst.offset.local_minus_utc() - self.std.offset.local_minus_utc();
+        let delta = Duration::seconds(delta as i64);
+        let gap_start = date_time(rule.start.day.date_in(naive.year()), rule.start.time);
+        let amb_start = date_time(rule.end.day.date_in(naive.year()), rule.end.time) - delta;
+        let amb_end = date_time(rule.end.day.date_in(naive.year()), rule.end.time);
+
+        if *naive >= gap_start && *naive < gap_start + delta {
+            return LocalResult::None;
+        }
+        if *naive >= amb_start && *naive < amb_end {
+            return LocalResult::Ambiguous(
+                dst.offset.from_local_datetime(naive).single().expect("fixed offset"),
+                self.std.offset.from_local_datetime(naive).single().expect("fixed offset"),
+            );
+        }
+        let offset = if active(rule, naive, self.std.offset, dst.offset) {
+            dst.offset
+        } else {
+            self.std.offset
+        };
+        offset.from_local_datetime(naive)
+    }
+}
+
+fn date_time(day: NaiveDate, time: i32) -> NaiveDateTime {
+    day.and_time(chrono::NaiveTime::MIN) + Duration::seconds(time as i64)
+}
+
+/// Whether DST is in effect at `naive`, away from the gap/ambiguous
+/// edges (those are handled by the caller).
+///
+/// `naive` here is the real wall clock, exactly as the zone's
+/// inhabitants would read it (std before `start`/after `end`, dst
+/// between): at the `start` transition it jumps from `start.time` to
+/// `start.time + delta`, so the first dst reading is `start + delta`;
+/// at the `end` transition it falls from `end.time` to
+/// `end.time - delta`, so the last unambiguous dst reading is just
+/// under `end - delta`.
+fn active(rule: &DstRule, naive: &NaiveDateTime, std: FixedOffset, dst: FixedOffset) -> bool {
+    let delta = Duration::seconds((dst.local_minus_utc() - std.local_minus_utc()) as i64);
+    let start = date_time(rule.start.day.date_in(naive.year()), rule.start.time);
+    let end = date_time(rule.end.day.date_in(naive.year()), rule.end.time);
+    if start <= end {
+        *naive >= start + delta && *naive < end - delta
+    } else {
+        !(*naive >= end && *naive < start)
+    }
+}
+
+/// Whether DST is in effect at `naive`, an *approximated* wall clock
+/// that assumes std applies year-round (`offset_at`'s convention,
+/// used when only a UTC instant is available and the real wall clock
+/// -- which would tell us which offset already applies -- isn't known
+/// yet).
+///
+/// Unlike [`active`], this needs different shifts at the two
+/// boundaries: since std is assumed to apply continuously, this
+/// approximated clock already equals the real one at the instant dst
+/// *starts* (std was genuinely in effect up to then), so `start` needs
+/// no shift; but it still lags the real clock by `delta` once dst is
+/// actually in effect, so the instant dst *ends* still reads as
+/// `end - delta` here, same as in `active`.
+fn active_approx(rule: &DstRule, naive: &NaiveDateTime, std: FixedOffset, dst: FixedOffset) -> bool {
+    let delta = Duration::seconds((dst.local_minus_utc() - std.local_minus_utc()) as i64);
+    let start = date_time(rule.start.day.date_in(naive.year()), rule.start.time);
+    let end = date_time(rule.end.day.date_in(naive.year()), rule.end.time);
+    if start <= end {
+        *naive >= start && *naive < end - delta
+    } else {
+        !(*naive >= end - delta && *naive < start)
+    }
+}
+
+/// Parse a POSIX `TZ` string.
+///
+pub(crate) fn parse(s: &str) -> Result<PosixTz> {
+    let (_std_name, rest) = parse_name(s)?;
+    let (std_offset, rest) = parse_offset(rest)?;
+    let std = NamedOffset { offset: std_offset };
+    if rest.is_empty() {
+        return Ok(PosixTz { std, dst: None });
+    }
+
+    let (_dst_name, rest) = parse_name(rest)?;
+    let (dst_offset, rest) = if rest.starts_with(',') || rest.is_empty() {
+        // no explicit DST offset: one hour east of std, per POSIX default
+        let offset = FixedOffset::east_opt(std_offset.local_minus_utc() + 3600)
+            .ok_or_else(|| anyhow!("DST offset out of range in {:?}", s))?;
+        (offset, rest)
+    } else {
+        parse_offset(rest)?
+    };
+    let dst = NamedOffset { offset: dst_offset };
+
+    let rule = if let Some(rest) = rest.strip_prefix(',') {
+        let (start, rest) = parse_transition(rest)?;
+        let rest = rest
+            .strip_prefix(',')
+            .ok_or_else(|| anyhow!("expected ',' before end-of-DST rule in {:?}", s))?;
+        let (end, rest) = parse_transition(rest)?;
+        if !rest.is_empty() {
+            bail!("unexpected trailing characters {:?} in {:?}", rest, s);
+        }
+        DstRule { start, end }
+    } else if rest.is_empty() {
+        // no explicit rule: US defaults (2nd Sunday in March to 1st Sunday in November)
+        DstRule {
+            start: Transition {
+                day: DayRule::MonthWeekDay { month: 3, week: 2, weekday: 0 },
+                time: 2 * 3600,
+            },
+            end: Transition {
+                day: DayRule::MonthWeekDay { month: 11, week: 1, weekday: 0 },
+                time: 2 * 3600,
+            },
+        }
+    } else {
+        bail!("unexpected trailing characters {:?} in {:?}", rest, s);
+    };
+
+    Ok(PosixTz {
+        std,
+        dst: Some((dst, rule)),
+    })
+}
+
+/// Parse a `std`/`dst` name: either a `<...>`-quoted string, or 3 or
+/// more letters.
+fn parse_name(s: &str) -> Result<(String, &str)> {
+    if let Some(rest) = s.strip_prefix('<') {
+        let end = rest.find('>').ok_or_else(|| anyhow!("unterminated <...> name in {:?}", s))?;
+        Ok((rest[..end].to_string(), &rest[end + 1..]))
+    } else {
+        let end = s.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(s.len());
+        if end < 3 {
+            bail!("zone name {:?} must have at least 3 letters", s);
+        }
+        Ok((s[..end].to_string(), &s[end..]))
+    }
+}
+
+/// Parse a POSIX offset: `[+-]hh[:mm[:ss]]`, sign inverted (positive
+/// is west of UTC).
+fn parse_offset(s: &str) -> Result<(FixedOffset, &str)> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let end = s.find(|c: char| !c.is_ascii_digit() && c != ':').unwrap_or(s.len());
+    let (field, rest) = (&s[..end], &s[end..]);
+    if field.is_empty() {
+        bail!("missing offset in {:?}", s);
+    }
+    let mut parts = field.split(':');
+    let hh: i64 = parts.next().unwrap().parse()?;
+    let mm: i64 = parts.next().map(str::parse).transpose()?.unwrap_or(0);
+    let ss: i64 = parts.next().map(str::parse).transpose()?.unwrap_or(0);
+    let west = hh * 3600 + mm * 60 + ss;
+    // POSIX offsets name hours *west* of UTC; we store the usual
+    // east-of-UTC convention that `FixedOffset` uses.
+    let east = if negative { west } else { -west };
+    let offset = FixedOffset::east_opt(east as i32)
+        .ok_or_else(|| anyhow!("offset out of range in {:?}", field))?;
+    Ok((offset, rest))
+}
+
+/// Parse a `start`/`end` transition: a day rule, optionally followed
+/// by `/time`.
+fn parse_transition(s: &str) -> Result<(Transition, &str)> {
+    let (day, rest) = parse_day_rule(s)?;
+    let (time, rest) = if let Some(rest) = rest.strip_prefix('/') {
+        parse_time_of_day(rest)?
+    } else {
+        (2 * 3600, rest)
+    };
+    Ok((Transition { day, time }, rest))
+}
+
+fn parse_day_rule(s: &str) -> Result<(DayRule, &str)> {
+    if let Some(rest) = s.strip_prefix('M') {
+        let (month, rest) = parse_uint(rest)?;
+        let rest = rest.strip_prefix('.').ok_or_else(|| anyhow!("expected '.' in Mm.w.d rule {:?}", s))?;
+        let (week, rest) = parse_uint(rest)?;
+        let rest = rest.strip_prefix('.').ok_or_else(|| anyhow!("expected '.' in Mm.w.d rule {:?}", s))?;
+        let (weekday, rest) = parse_uint(rest)?;
+        if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+            bail!("Mm.w.d rule {:?} out of range", s);
+        }
+        Ok((DayRule::MonthWeekDay { month, week, weekday }, rest))
+    } else if let Some(rest) = s.strip_prefix('J') {
+        let (day, rest) = parse_uint(rest)?;
+        if !(1..=365).contains(&day) {
+            bail!("Jn rule {:?} out of range", s);
+        }
+        Ok((DayRule::JulianNoLeap(day as u16), rest))
+    } else {
+        let (day, rest) = parse_uint(s)?;
+        if day > 365 {
+            bail!("n rule {:?} out of range", s);
+        }
+        Ok((DayRule::Julian(day as u16), rest))
+    }
+}
+
+fn parse_uint(s: &str) -> Result<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if end == 0 {
+        bail!("expected a number in {:?}", s);
+    }
+    Ok((s[..end].parse()?, &s[end..]))
+}
+
+/// Parse a `/time` field: `[+-]hh[:mm[:ss]]`, *not* sign-inverted
+/// (unlike `offset`, per POSIX).
+fn parse_time_of_day(s: &str) -> Result<(i32, &str)> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let end = s.find(|c: char| !c.is_ascii_digit() && c != ':').unwrap_or(s.len());
+    let (field, rest) = (&s[..end], &s[end..]);
+    if field.is_empty() {
+        bail!("missing time in {:?}", s);
+    }
+    let mut parts = field.split(':');
+    let hh: i64 = parts.next().unwrap().parse()?;
+    let mm: i64 = parts.next().map(str::parse).transpose()?.unwrap_or(0);
+    let ss: i64 = parts.next().map(str::parse).transpose()?.unwrap_or(0);
+    let secs = hh * 3600 + mm * 60 + ss;
+    Ok(((if negative { -secs } else { secs }) as i32, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDateTime, Offset};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("EST5EDT,M3.2.0/2,M11.1.0/2")]
+    #[case("<+07>-7")]
+    #[case("CET-1CEST,M3.5.0,M10.5.0/3")]
+    #[case("UTC0")]
+    #[case("NZST-12NZDT,M9.5.0,M4.1.0/3")]
+    fn test_parse_ok(#[case] s: &str) {
+        assert!(parse(s).is_ok());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("ES5EDT")] // too short a name
+    #[case("EST")] // missing offset
+    #[case("EST5EDT,M13.2.0/2,M11.1.0/2")] // month out of range
+    #[case("EST5EDT,M3.2.0/2")] // missing end rule
+    fn test_parse_nok(#[case] s: &str) {
+        assert!(parse(s).is_err());
+    }
+
+    #[rstest]
+    #[case("2021-07-21T16:00:00", -4 * 3600)] // summer: EDT
+    #[case("2021-01-21T16:00:00", -5 * 3600)] // winter: EST
+    fn test_us_offset(#[case] naive: &str, #[case] offset: i32) {
+        let tz = parse("EST5EDT,M3.2.0/2,M11.1.0/2").unwrap();
+        let naive: NaiveDateTime = naive.parse().unwrap();
+        match tz.resolve_local(&naive) {
+            LocalResult::Single(dt) => assert_eq!(dt.offset().local_minus_utc(), offset),
+            other => panic!("expected a single result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_us_gap_and_ambiguous() {
+        let tz = parse("EST5EDT,M3.2.0/2,M11.1.0/2").unwrap();
+
+        // 2021-03-14 02:30 doesn't exist: clocks spring forward at 02:00.
+        let gap: NaiveDateTime = "2021-03-14T02:30:00".parse().unwrap();
+        assert!(matches!(tz.resolve_local(&gap), LocalResult::None));
+
+        // 2021-11-07 01:30 happens twice: clocks fall back at 02:00 EDT.
+        let ambiguous: NaiveDateTime = "2021-11-07T01:30:00".parse().unwrap();
+        match tz.resolve_local(&ambiguous) {
+            LocalResult::Ambiguous(early, late) => {
+                assert_eq!(early.offset().local_minus_utc(), -4 * 3600);
+                assert_eq!(late.offset().local_minus_utc(), -5 * 3600);
+            }
+            other => panic!("expected an ambiguous result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fixed_offset_no_dst() {
+        let tz = parse("<+07>-7").unwrap();
+        let naive: NaiveDateTime = "2021-07-21T16:00:00".parse().unwrap();
+        match tz.resolve_local(&naive) {
+            LocalResult::Single(dt) => assert_eq!(dt.offset().local_minus_utc(), 7 * 3600),
+            other => panic!("expected a single result, got {:?}", other),
+        }
+    }
+
+    /// Scan UTC instants spanning `transition`, asserting `offset_at`
+    /// agrees with both `chrono_tz`'s ground truth and `resolve_local`
+    /// (fed the real wall clock `offset_at` just computed) at each one.
+    ///
+    /// This is what caught `offset_at` using the wrong DST boundary
+    /// shift for its approximated-as-std wall clock: the old code
+    /// agreed with ground truth everywhere except the hour right after
+    /// each transition, which a test that only checked `resolve_local`
+    /// (already fed a correct, real wall clock) could never reach.
+    fn assert_offset_at_matches(tz: &PosixTz, ground_truth: chrono_tz::Tz, transition: DateTime<Utc>) {
+        for minutes in -90..90 {
+            let instant = transition + Duration::minutes(minutes);
+            let expected = instant.with_timezone(&ground_truth).offset().fix();
+            let actual = tz.offset_at(&instant);
+            assert_eq!(actual, expected, "offset_at disagrees with chrono_tz at {instant}");
+
+            // The real wall clock this instant reads as repeats itself
+            // across the fall-back transition, so `resolve_local` may
+            // report it as ambiguous; either of its two candidate
+            // offsets agreeing with `expected` (not necessarily both)
+            // confirms `actual` is one `resolve_local` itself would
+            // produce for this wall clock.
+            let naive = instant.naive_utc() + Duration::seconds(actual.local_minus_utc() as i64);
+            let resolved = match tz.resolve_local(&naive) {
+                LocalResult::Single(dt) => vec![dt],
+                LocalResult::Ambiguous(a, b) => vec![a, b],
+                LocalResult::None => panic!("{naive} unexpectedly falls in the spring-forward gap"),
+            };
+            assert!(
+                resolved.iter().any(|dt| dt.offset().local_minus_utc() == expected.local_minus_utc()),
+                "resolve_local({naive}) = {resolved:?} doesn't include offset {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_offset_at_non_wrapped_transition() {
+        // America/New_York springs forward 2021-03-14 02:00 EST -> 03:00
+        // EDT, which is 2021-03-14T07:00:00Z.
+        let tz = parse("EST5EDT,M3.2.0/2,M11.1.0/2").unwrap();
+        let transition: DateTime<Utc> = "2021-03-14T07:00:00Z".parse().unwrap();
+        assert_offset_at_matches(&tz, chrono_tz::America::New_York, transition);
+    }
+
+    #[test]
+    fn test_offset_at_wrapped_transition() {
+        // Pacific/Auckland (Southern Hemisphere) falls back 2021-04-04
+        // 03:00 NZDT -> 02:00 NZST, which is 2021-04-03T14:00:00Z.
+        let tz = parse("NZST-12NZDT,M9.5.0,M4.1.0/3").unwrap();
+        let transition: DateTime<Utc> = "2021-04-03T14:00:00Z".parse().unwrap();
+        assert_offset_at_matches(&tz, chrono_tz::Pacific::Auckland, transition);
+    }
+}